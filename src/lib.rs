@@ -14,32 +14,274 @@ use std::hash::BuildHasher;
 
 use rustc_hash::FxBuildHasher;
 
-pub struct Interner<T> {
-    set: RwLock<HashTable<NonNull<u8>>>,
+pub mod lockfree;
+pub mod sharded;
+pub mod sync;
+pub mod unsync;
+
+/// A cheap, `Copy` handle to a value previously interned by an [`Interner`].
+///
+/// Unlike `&T`, a handle carries no lifetime and is just a `u32` index, so it
+/// can be stored as a key in other maps or passed around freely. Equality,
+/// ordering and hashing only ever compare the index, which is sound because
+/// the interner deduplicates values before an index is assigned.
+pub struct Interned<T> {
+    index: u32,
+    __marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Interned<T> {
+    /// Returns the raw index backing this handle.
+    #[must_use]
+    pub const fn index(self) -> u32 {
+        self.index
+    }
+
+    pub(crate) const fn from_index(index: u32) -> Self {
+        Self { index, __marker: PhantomData }
+    }
+}
+
+impl<T> Clone for Interned<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for Interned<T> {}
+
+impl<T> PartialEq for Interned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+impl<T> Eq for Interned<T> {}
+
+impl<T> PartialOrd for Interned<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<T> Ord for Interned<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.index.cmp(&other.index)
+    }
+}
+
+impl<T> Hash for Interned<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Interned<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Interned").field(&self.index).finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for Interned<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.index.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for Interned<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u32::deserialize(deserializer).map(Self::from_index)
+    }
+}
+
+/// Implemented for unsized types whose content can be bulk-copied into an
+/// [`Interner`]'s arena, letting them be interned via [`Interner::intern_ref`]
+/// without first being boxed into an owned `String`/`Vec<U>`.
+pub trait InternRef: Hash + Eq {
+    #[doc(hidden)]
+    fn copy_into(&self, arena: &Bump) -> NonNull<u8>;
+    #[doc(hidden)]
+    fn ref_len(&self) -> usize;
+    /// # Safety
+    /// `ptr` and `len` must have been produced by a prior call to [`InternRef::copy_into`]
+    /// (and [`InternRef::ref_len`]) on an equivalent value, and the pointee must still be live.
+    #[doc(hidden)]
+    unsafe fn from_raw_parts<'a>(ptr: NonNull<u8>, len: usize) -> &'a Self;
+}
+
+impl InternRef for str {
+    fn copy_into(&self, arena: &Bump) -> NonNull<u8> {
+        NonNull::from(arena.alloc_str(self)).cast()
+    }
+    fn ref_len(&self) -> usize {
+        self.len()
+    }
+    unsafe fn from_raw_parts<'a>(ptr: NonNull<u8>, len: usize) -> &'a Self {
+        unsafe { std::str::from_utf8_unchecked(std::slice::from_raw_parts(ptr.as_ptr(), len)) }
+    }
+}
+
+impl<U: Copy + Hash + Eq> InternRef for [U] {
+    fn copy_into(&self, arena: &Bump) -> NonNull<u8> {
+        NonNull::from(arena.alloc_slice_copy(self)).cast()
+    }
+    fn ref_len(&self) -> usize {
+        self.len()
+    }
+    unsafe fn from_raw_parts<'a>(ptr: NonNull<u8>, len: usize) -> &'a Self {
+        unsafe { std::slice::from_raw_parts(ptr.as_ptr().cast::<U>(), len) }
+    }
+}
+
+/// Reconstructs a `&T` from the type-erased `(ptr, len)` pairs stored in an
+/// interner's tables, so the `Debug` impls can walk both the `ids` (`Sized`,
+/// `len` unused) and `ref_entries` (unsized [`InternRef`]) storage generically.
+pub(crate) trait DebugEntry {
+    unsafe fn debug_entry<'a>(ptr: NonNull<u8>, len: usize) -> &'a Self;
+}
+
+impl<T> DebugEntry for T {
+    unsafe fn debug_entry<'a>(ptr: NonNull<u8>, _len: usize) -> &'a Self {
+        unsafe { ptr.cast::<T>().as_ref() }
+    }
+}
+
+impl DebugEntry for str {
+    unsafe fn debug_entry<'a>(ptr: NonNull<u8>, len: usize) -> &'a Self {
+        unsafe { <str as InternRef>::from_raw_parts(ptr, len) }
+    }
+}
+
+impl<U: Copy + Hash + Eq> DebugEntry for [U] {
+    unsafe fn debug_entry<'a>(ptr: NonNull<u8>, len: usize) -> &'a Self {
+        unsafe { <[U] as InternRef>::from_raw_parts(ptr, len) }
+    }
+}
+
+struct RefEntry {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+struct Table {
+    // keyed on a value's hash, payload is the index of its entry in `ids`
+    set: HashTable<u32>,
+    // `NonNull<u8>` is a reference into the arena, indexed by `Interned::index`
+    ids: Vec<NonNull<u8>>,
+    // storage for values interned through `intern_ref`, kept separate since those
+    // need a length alongside the pointer to reconstruct a fat pointer
+    ref_set: HashTable<u32>,
+    ref_entries: Vec<RefEntry>,
+}
+
+impl Table {
+    const fn new() -> Self {
+        Self {
+            set: HashTable::new(),
+            ids: Vec::new(),
+            ref_set: HashTable::new(),
+            ref_entries: Vec::new(),
+        }
+    }
+}
+
+pub struct Interner<T: ?Sized> {
+    table: RwLock<Table>,
     arena: OnceLock<Mutex<Bump>>,
     __marker: PhantomData<T>,
 }
 
-impl<T: fmt::Debug> fmt::Debug for Interner<T> {
+impl<T: ?Sized + fmt::Debug + DebugEntry> fmt::Debug for Interner<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut debug_map = f.debug_set();
-        let Some(inner) = self.set.try_read() else {
-            return debug_map.finish_non_exhaustive();
+        let mut debug_set = f.debug_set();
+        let Some(table) = self.table.try_read() else {
+            return debug_set.finish_non_exhaustive();
         };
-        debug_map.entries(&*inner).finish()
+        debug_set.entries(table.ids.iter().map(|&ptr| unsafe { T::debug_entry(ptr, 0) }));
+        debug_set.entries(
+            table.ref_entries.iter().map(|entry| unsafe { T::debug_entry(entry.ptr, entry.len) }),
+        );
+        debug_set.finish()
     }
 }
 
-impl<T> Default for Interner<T> {
+impl<T: ?Sized> Default for Interner<T> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<T> Interner<T> {
+impl<T: ?Sized> Interner<T> {
     #[must_use]
     pub const fn new() -> Self {
-        Self { set: RwLock::new(HashTable::new()), arena: OnceLock::new(), __marker: PhantomData }
+        Self { table: RwLock::new(Table::new()), arena: OnceLock::new(), __marker: PhantomData }
+    }
+
+    /// Returns the number of entries in the interner
+    pub fn len(&self) -> usize {
+        let table = self.table.read();
+        table.ids.len() + table.ref_entries.len()
+    }
+
+    /// Returns `true` if the interner contains no elements
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: ?Sized + InternRef> Interner<T> {
+    /// Will return a reference to an equivalent value if it already exists
+    #[must_use]
+    pub fn try_resolve_ref(&self, value: &T) -> Option<&T> {
+        let hash = FxBuildHasher.hash_one(value);
+
+        let table = self.table.read();
+        unsafe {
+            table
+                .ref_set
+                .find(hash, |&idx| {
+                    let entry = &table.ref_entries[idx as usize];
+                    T::from_raw_parts(entry.ptr, entry.len) == value
+                })
+                .map(|&idx| {
+                    let entry = &table.ref_entries[idx as usize];
+                    T::from_raw_parts(entry.ptr, entry.len)
+                })
+        }
+    }
+
+    /// Returns a reference to either the value provided, or an equivalent value that was
+    /// already inserted, copying `value`'s content into the arena rather than taking
+    /// ownership of it.
+    #[expect(clippy::missing_panics_doc)]
+    pub fn intern_ref(&self, value: &T) -> &T {
+        let hash = FxBuildHasher.hash_one(value);
+
+        let table = self.table.upgradable_read();
+        let found = unsafe {
+            table.ref_set.find(hash, |&idx| {
+                let entry = &table.ref_entries[idx as usize];
+                T::from_raw_parts(entry.ptr, entry.len) == value
+            })
+        };
+        if let Some(&idx) = found {
+            let entry = &table.ref_entries[idx as usize];
+            return unsafe { T::from_raw_parts(entry.ptr, entry.len) };
+        }
+
+        let arena = self.arena.get_or_init(Mutex::default).lock().unwrap();
+        let ptr = value.copy_into(&arena);
+        let len = value.ref_len();
+        drop(arena);
+
+        let mut table = RwLockUpgradableReadGuard::upgrade(table);
+        let Table { ref_set, ref_entries, .. } = &mut *table;
+        let idx = u32::try_from(ref_entries.len()).expect("too many interned values");
+        ref_entries.push(RefEntry { ptr, len });
+        ref_set.insert_unique(hash, idx, |&idx| unsafe {
+            let entry = &ref_entries[idx as usize];
+            FxBuildHasher.hash_one(T::from_raw_parts(entry.ptr, entry.len))
+        });
+        unsafe { T::from_raw_parts(ptr, len) }
     }
 }
 
@@ -51,38 +293,124 @@ impl<T: Hash + Eq> Interner<T> {
         Q: ?Sized + Hash + Eq,
     {
         let hash = FxBuildHasher.hash_one(value);
+        self.try_resolve_with(value, hash)
+    }
 
+    /// Like [`Interner::try_resolve`], but takes an already-computed hash rather than
+    /// recomputing one, for callers (e.g. [`crate::sharded`]) that used the hash to pick
+    /// this interner in the first place.
+    pub(crate) fn try_resolve_with<Q>(&self, value: &Q, hash: u64) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let table = self.table.read();
         unsafe {
-            self.set
-                .read()
-                .find(hash, |cached| T::borrow(cached.cast().as_ref()) == value)
-                .map(|ptr| ptr.cast().as_ref())
+            table
+                .set
+                .find(hash, |&idx| T::borrow(table.ids[idx as usize].cast().as_ref()) == value)
+                .map(|&idx| table.ids[idx as usize].cast().as_ref())
         }
     }
 
-    #[expect(clippy::missing_panics_doc)]
-    pub fn intern(&self, value: T) -> &T {
-        let hash = FxBuildHasher.hash_one(&value);
+    /// Resolves a handle previously returned by [`Interner::intern_id`] back into a reference.
+    #[must_use]
+    pub fn resolve(&self, id: Interned<T>) -> &T {
+        let table = self.table.read();
+        unsafe { table.ids[id.index as usize].cast().as_ref() }
+    }
 
-        let set = self.set.upgradable_read();
-        unsafe {
-            if let Some(cached) = set.find(hash, |cached| cached.cast::<T>().as_ref() == &value) {
-                return cached.cast().as_ref();
-            }
+    /// Like [`Interner::intern`], but takes an already-computed hash rather than
+    /// recomputing one, and returns the index of the entry in `ids` rather than a
+    /// reference. Shared by [`Interner::intern_with`] and [`Interner::intern_id_with`].
+    pub(crate) fn intern_index(&self, hash: u64, value: T) -> u32 {
+        let table = self.table.upgradable_read();
+        let found = unsafe {
+            table.set.find(hash, |&idx| table.ids[idx as usize].cast::<T>().as_ref() == &value)
+        };
+        if let Some(&idx) = found {
+            return idx;
         }
 
         let arena = self.arena.get_or_init(Mutex::default).lock().unwrap();
-        let cached = NonNull::from(arena.alloc(value)).cast();
+        let ptr = NonNull::from(arena.alloc(value)).cast();
         drop(arena);
-        let mut set = RwLockUpgradableReadGuard::upgrade(set);
-        set.insert_unique(hash, cached, |t| FxBuildHasher.hash_one(t));
-        unsafe { cached.cast().as_ref() }
+
+        let mut table = RwLockUpgradableReadGuard::upgrade(table);
+        let Table { set, ids, .. } = &mut *table;
+        let idx = u32::try_from(ids.len()).expect("too many interned values");
+        ids.push(ptr);
+        set.insert_unique(hash, idx, |&idx| unsafe {
+            FxBuildHasher.hash_one(ids[idx as usize].cast::<T>().as_ref())
+        });
+        idx
+    }
+
+    pub fn intern(&self, value: T) -> &T {
+        let hash = FxBuildHasher.hash_one(&value);
+        self.intern_with(hash, value)
+    }
+
+    /// Like [`Interner::intern`], but takes an already-computed hash rather than
+    /// recomputing one, for callers (e.g. [`crate::sharded`]) that used the hash to pick
+    /// this interner in the first place.
+    pub(crate) fn intern_with(&self, hash: u64, value: T) -> &T {
+        let idx = self.intern_index(hash, value);
+        let table = self.table.read();
+        unsafe { table.ids[idx as usize].cast().as_ref() }
+    }
+
+    /// Interns `value`, returning a cheap [`Copy`] handle instead of a reference.
+    ///
+    /// The handle can later be turned back into `&T` via [`Interner::resolve`].
+    pub fn intern_id(&self, value: T) -> Interned<T> {
+        let hash = FxBuildHasher.hash_one(&value);
+        self.intern_id_with(hash, value)
+    }
+
+    /// Like [`Interner::intern_id`], but takes an already-computed hash rather than
+    /// recomputing one, for callers (e.g. [`crate::sharded`]) that used the hash to pick
+    /// this interner in the first place.
+    pub(crate) fn intern_id_with(&self, hash: u64, value: T) -> Interned<T> {
+        Interned::from_index(self.intern_index(hash, value))
     }
 }
 
 // FIXME: this might be overly restrictive?
-unsafe impl<T: Send + Sync> Send for Interner<T> {}
-unsafe impl<T: Send + Sync> Sync for Interner<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Send for Interner<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for Interner<T> {}
+
+/// Serializes as the sequence of distinct values currently interned, in
+/// [`Interner::intern_id`] order, so that [`Interned`] handles serialized
+/// alongside this interner remain valid after a deserialize round-trip.
+#[cfg(feature = "serde")]
+impl<T: Hash + Eq + serde::Serialize> serde::Serialize for Interner<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+
+        let table = self.table.read();
+        let mut seq = serializer.serialize_seq(Some(table.ids.len()))?;
+        for &ptr in &table.ids {
+            seq.serialize_element(unsafe { ptr.cast::<T>().as_ref() })?;
+        }
+        seq.end()
+    }
+}
+
+/// Deserializes a sequence of values, re-interning each one into a fresh
+/// interner so that indices (and thus any [`Interned`] handles) are
+/// restored in the same order they were serialized.
+#[cfg(feature = "serde")]
+impl<'de, T: Hash + Eq + serde::Deserialize<'de>> serde::Deserialize<'de> for Interner<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let interner = Self::new();
+        for value in values {
+            interner.intern_id(value);
+        }
+        Ok(interner)
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -112,4 +440,42 @@ mod tests {
         let array = interner.intern(Type::Array(int));
         println!("{array:?}");
     }
+
+    #[test]
+    fn interned_handles() {
+        let interner = Interner::new();
+
+        let a = interner.intern_id(1);
+        let b = interner.intern_id(1);
+        let c = interner.intern_id(2);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(interner.resolve(a), &1);
+        assert_eq!(interner.resolve(c), &2);
+    }
+
+    #[test]
+    fn intern_str() {
+        let interner: Interner<str> = Interner::new();
+
+        let a: *const str = interner.intern_ref("hello");
+        let b: *const str = interner.intern_ref("hello");
+        interner.intern_ref("world");
+
+        assert_eq!(a, b);
+        assert_eq!(interner.try_resolve_ref("hello"), Some("hello"));
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    fn intern_slice() {
+        let interner: Interner<[u8]> = Interner::new();
+
+        let a = interner.intern_ref(&[1, 2, 3][..]);
+        let b = interner.intern_ref(&[1, 2, 3][..]);
+
+        assert_eq!(a.as_ptr(), b.as_ptr());
+        assert_eq!(interner.try_resolve_ref(&[1, 2, 3]), Some(&[1, 2, 3][..]));
+    }
 }
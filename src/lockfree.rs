@@ -0,0 +1,303 @@
+//! A lock-free-read interner: writes are serialized by a [`Mutex`], but the
+//! table itself is an atomically-published, epoch-reclaimed structure, so
+//! `try_resolve`/`intern`'s lookup never takes a lock and never blocks a
+//! concurrent writer.
+//!
+//! This is a real trade-off, not a free upgrade over [`crate::sharded`] or
+//! [`crate::unsync`]: because readers may be dereferencing the published
+//! table at any moment, a cache-miss `intern` can never mutate it in place.
+//! Every miss clones the *entire* table before inserting into the clone and
+//! republishing it, so total insertion cost for `n` distinct values is
+//! `O(n^2)`, not amortized `O(n)`. Prefer this variant when reads vastly
+//! outnumber distinct values interned; for write-heavy or large workloads,
+//! [`crate::sharded`] or [`crate::unsync`] (behind your own lock) scale better.
+
+use std::{
+    borrow::Borrow,
+    fmt,
+    hash::{BuildHasher, Hash},
+    marker::PhantomData,
+    ptr::NonNull,
+    sync::{atomic::Ordering, Mutex},
+};
+
+use bumpalo::Bump;
+use crossbeam_epoch::{self as epoch, Atomic, Owned};
+use hashbrown::HashTable;
+use rustc_hash::FxBuildHasher;
+
+use crate::Interned;
+
+struct Table {
+    // keyed on a value's hash, payload is the index of its entry in `ids`
+    set: HashTable<u32>,
+    // `NonNull<u8>` is a reference into the arena, indexed by `Interned::index`
+    ids: Vec<NonNull<u8>>,
+}
+
+pub struct Interner<T> {
+    table: Atomic<Table>,
+    // serializes writers; also owns the arena, since allocation only ever
+    // happens while holding this lock
+    arena: Mutex<Bump>,
+    __marker: PhantomData<T>,
+}
+
+impl<T> Default for Interner<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Interner<T> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            table: Atomic::new(Table { set: HashTable::new(), ids: Vec::new() }),
+            arena: Mutex::new(Bump::new()),
+            __marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of entries in the interner
+    pub fn len(&self) -> usize {
+        let guard = epoch::pin();
+        unsafe { self.table.load(Ordering::Acquire, &guard).deref() }.ids.len()
+    }
+
+    /// Returns `true` if the interner contains no elements
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Hash + Eq> Interner<T> {
+    /// Will return a reference to an equivalent value if it already exists.
+    ///
+    /// This never blocks: it pins the current epoch, reads the published
+    /// table snapshot and probes it, all without taking a lock.
+    #[must_use]
+    pub fn try_resolve<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let hash = FxBuildHasher.hash_one(value);
+        self.try_resolve_with(value, hash)
+    }
+
+    fn try_resolve_with<Q>(&self, value: &Q, hash: u64) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let guard = epoch::pin();
+        let table = unsafe { self.table.load(Ordering::Acquire, &guard).deref() };
+        unsafe {
+            table
+                .set
+                .find(hash, |&idx| T::borrow(table.ids[idx as usize].cast().as_ref()) == value)
+                .map(|&idx| table.ids[idx as usize].cast().as_ref())
+        }
+    }
+
+    fn try_resolve_id_with(&self, value: &T, hash: u64) -> Option<Interned<T>> {
+        let guard = epoch::pin();
+        let table = unsafe { self.table.load(Ordering::Acquire, &guard).deref() };
+        unsafe {
+            table
+                .set
+                .find(hash, |&idx| table.ids[idx as usize].cast::<T>().as_ref() == value)
+                .map(|&idx| Interned::from_index(idx))
+        }
+    }
+
+    /// Resolves a handle previously returned by [`Interner::intern_id`] back into a reference.
+    ///
+    /// Like [`Interner::try_resolve`], this never blocks.
+    #[must_use]
+    pub fn resolve(&self, id: Interned<T>) -> &T {
+        let guard = epoch::pin();
+        let table = unsafe { self.table.load(Ordering::Acquire, &guard).deref() };
+        unsafe { table.ids[id.index() as usize].cast().as_ref() }
+    }
+
+    /// Returns a reference to either the value provided, or an equivalent value that was already
+    /// inserted.
+    ///
+    /// On a cache miss this clones the entire table to build the next published generation,
+    /// so inserting `n` distinct values costs `O(n^2)` overall; see the module docs.
+    pub fn intern(&self, value: T) -> &T {
+        let hash = FxBuildHasher.hash_one(&value);
+
+        if let Some(cached) = self.try_resolve_with(&value, hash) {
+            return cached;
+        }
+
+        let idx = self.intern_index(hash, value);
+
+        let guard = epoch::pin();
+        let table = unsafe { self.table.load(Ordering::Acquire, &guard).deref() };
+        unsafe { table.ids[idx as usize].cast().as_ref() }
+    }
+
+    /// Interns `value`, returning a cheap [`Copy`](std::marker::Copy) handle instead of a
+    /// reference.
+    ///
+    /// The handle can later be turned back into `&T` via [`Interner::resolve`].
+    pub fn intern_id(&self, value: T) -> Interned<T> {
+        let hash = FxBuildHasher.hash_one(&value);
+
+        if let Some(id) = self.try_resolve_id_with(&value, hash) {
+            return id;
+        }
+
+        Interned::from_index(self.intern_index(hash, value))
+    }
+
+    /// On a cache miss this clones the entire table to build the next published generation,
+    /// so inserting `n` distinct values costs `O(n^2)` overall; see the module docs.
+    fn intern_index(&self, hash: u64, value: T) -> u32 {
+        // only one writer proceeds past this point at a time
+        let arena = self.arena.lock().unwrap();
+
+        let guard = epoch::pin();
+        let current = unsafe { self.table.load(Ordering::Acquire, &guard).deref() };
+
+        // someone may have inserted an equal value while we were waiting on `arena`
+        if let Some(&idx) =
+            unsafe { current.set.find(hash, |&idx| current.ids[idx as usize].cast::<T>().as_ref() == &value) }
+        {
+            return idx;
+        }
+
+        let ptr = NonNull::from(arena.alloc(value)).cast();
+
+        // No safe way to grow/insert into `current` in place: a concurrent `try_resolve`
+        // may be probing it via `deref()` right now with no synchronization of its own, so
+        // every miss pays for a full clone of the table, not just ones that trigger a resize.
+        let mut new_set = current.set.clone();
+        let mut new_ids = current.ids.clone();
+        let idx = u32::try_from(new_ids.len()).expect("too many interned values");
+        new_ids.push(ptr);
+        new_set.insert_unique(hash, idx, |&idx| unsafe {
+            FxBuildHasher.hash_one(new_ids[idx as usize].cast::<T>().as_ref())
+        });
+
+        let new_table = Owned::new(Table { set: new_set, ids: new_ids }).into_shared(&guard);
+        let old_table = self.table.swap(new_table, Ordering::AcqRel, &guard);
+        // Safety: `old_table` was just unpublished by the swap above, so no new reader can
+        // observe it; `defer_destroy` reclaims it once every reader pinned before the swap
+        // has released its guard.
+        unsafe { guard.defer_destroy(old_table) };
+
+        idx
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Interner<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let guard = epoch::pin();
+        let table = unsafe { self.table.load(Ordering::Acquire, &guard).deref() };
+        f.debug_set().entries(table.ids.iter().map(|&ptr| unsafe { ptr.cast::<T>().as_ref() })).finish()
+    }
+}
+
+unsafe impl<T: Send + Sync> Send for Interner<T> {}
+unsafe impl<T: Send + Sync> Sync for Interner<T> {}
+
+impl<T> Drop for Interner<T> {
+    fn drop(&mut self) {
+        // Safety: `&mut self` guarantees no concurrent readers or writers hold a
+        // reference into `self.table`, so the currently-published generation can be
+        // reclaimed immediately without waiting on the epoch collector.
+        unsafe { drop(self.table.load(Ordering::Relaxed, epoch::unprotected()).into_owned()) };
+    }
+}
+
+/// Serializes as the sequence of distinct values currently interned, in
+/// [`Interner::intern_id`] order, so that [`Interned`] handles serialized
+/// alongside this interner remain valid after a deserialize round-trip.
+#[cfg(feature = "serde")]
+impl<T: Hash + Eq + serde::Serialize> serde::Serialize for Interner<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+
+        let guard = epoch::pin();
+        let table = unsafe { self.table.load(Ordering::Acquire, &guard).deref() };
+        let mut seq = serializer.serialize_seq(Some(table.ids.len()))?;
+        for &ptr in &table.ids {
+            seq.serialize_element(unsafe { ptr.cast::<T>().as_ref() })?;
+        }
+        seq.end()
+    }
+}
+
+/// Deserializes a sequence of values, re-interning each one into a fresh
+/// interner so that indices (and thus any [`Interned`] handles) are
+/// restored in the same order they were serialized.
+#[cfg(feature = "serde")]
+impl<'de, T: Hash + Eq + serde::Deserialize<'de>> serde::Deserialize<'de> for Interner<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let interner = Self::new();
+        for value in values {
+            interner.intern_id(value);
+        }
+        Ok(interner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn addr() {
+        let interner = Interner::new();
+
+        let a1: *const _ = interner.intern(1);
+        let b1: *const _ = interner.intern(1);
+        interner.intern(2);
+
+        assert!(interner.try_resolve(&1) == Some(&1));
+        assert_eq!(a1.addr(), b1.addr());
+    }
+
+    #[test]
+    fn interned_handles() {
+        let interner = Interner::new();
+
+        let a = interner.intern_id(1);
+        let b = interner.intern_id(1);
+        let c = interner.intern_id(2);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(interner.resolve(a), &1);
+        assert_eq!(interner.resolve(c), &2);
+    }
+
+    #[test]
+    fn concurrent_interning() {
+        use std::sync::Arc;
+
+        let interner = Arc::new(Interner::new());
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let interner = Arc::clone(&interner);
+                std::thread::spawn(move || {
+                    for i in 0..100 {
+                        interner.intern(i);
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(interner.len(), 100);
+    }
+}
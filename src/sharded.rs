@@ -0,0 +1,218 @@
+//! A sharded interner, trading the single lock + single arena of
+//! [`crate::Interner`] for `N` independently-locked shards to cut contention
+//! when many threads intern concurrently.
+
+use std::{
+    borrow::Borrow,
+    hash::{BuildHasher, Hash},
+    num::NonZeroUsize,
+};
+
+use rustc_hash::FxBuildHasher;
+
+pub struct Interner<T> {
+    // high bits of the hash select a shard, the shard's own `Interner` is handed
+    // the same hash and uses its low bits to select a bucket
+    shards: Box<[crate::Interner<T>]>,
+    shift: u32,
+}
+
+impl<T> Default for Interner<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Interner<T> {
+    /// Creates an interner sharded across a power-of-two number of shards
+    /// derived from the available parallelism.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_shards(default_shard_count())
+    }
+
+    /// Creates an interner with (at least) the given number of shards,
+    /// rounded up to the next power of two.
+    #[must_use]
+    pub fn with_shards(shards: usize) -> Self {
+        let shards = shards.max(1).next_power_of_two();
+        Self {
+            shards: (0..shards).map(|_| crate::Interner::new()).collect(),
+            shift: 64 - shards.trailing_zeros(),
+        }
+    }
+
+    fn shard_index(&self, hash: u64) -> usize {
+        // `checked_shr` avoids a shift overflow (a panic in debug builds, a masked shift
+        // in release) when there is a single shard, where `shift == 64`
+        hash.checked_shr(self.shift).unwrap_or(0) as usize
+    }
+
+    fn shard(&self, hash: u64) -> &crate::Interner<T> {
+        &self.shards[self.shard_index(hash)]
+    }
+
+    /// Returns the number of entries in the interner
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(crate::Interner::len).sum()
+    }
+
+    /// Returns `true` if the interner contains no elements
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Hash + Eq> Interner<T> {
+    #[must_use]
+    pub fn try_resolve<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        // the high bits of this single hash pick the shard, the low bits are reused by
+        // the shard's own `HashTable` for its in-shard bucket lookup
+        let hash = FxBuildHasher.hash_one(value);
+        self.shard(hash).try_resolve_with(value, hash)
+    }
+
+    pub fn intern(&self, value: T) -> &T {
+        let hash = FxBuildHasher.hash_one(&value);
+        self.shard(hash).intern_with(hash, value)
+    }
+
+    /// Interns `value`, returning a cheap [`crate::Interned`] handle instead of a reference.
+    ///
+    /// The handle is only valid for resolution against the same shard, so callers must
+    /// resolve it through this same [`Interner`].
+    #[expect(clippy::missing_panics_doc)]
+    pub fn intern_id(&self, value: T) -> ShardedId<T> {
+        let hash = FxBuildHasher.hash_one(&value);
+        let shard = u32::try_from(self.shard_index(hash)).expect("shard count fits in a u32");
+        ShardedId { shard, index: self.shards[shard as usize].intern_id_with(hash, value) }
+    }
+
+    /// Resolves a handle previously returned by [`Interner::intern_id`] back into a reference.
+    #[must_use]
+    pub fn resolve(&self, id: ShardedId<T>) -> &T {
+        self.shards[id.shard as usize].resolve(id.index)
+    }
+}
+
+/// A cheap, `Copy` handle returned by [`Interner::intern_id`], valid only
+/// against the [`Interner`] that produced it.
+pub struct ShardedId<T> {
+    shard: u32,
+    index: crate::Interned<T>,
+}
+
+impl<T> Clone for ShardedId<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for ShardedId<T> {}
+
+impl<T> PartialEq for ShardedId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.shard == other.shard && self.index == other.index
+    }
+}
+impl<T> Eq for ShardedId<T> {}
+
+impl<T> PartialOrd for ShardedId<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<T> Ord for ShardedId<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.shard, self.index).cmp(&(other.shard, other.index))
+    }
+}
+
+impl<T> std::hash::Hash for ShardedId<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.shard.hash(state);
+        self.index.hash(state);
+    }
+}
+
+impl<T> std::fmt::Debug for ShardedId<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShardedId").field("shard", &self.shard).field("index", &self.index).finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for ShardedId<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.shard, self.index).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for ShardedId<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (shard, index) = <(u32, crate::Interned<T>)>::deserialize(deserializer)?;
+        Ok(Self { shard, index })
+    }
+}
+
+fn default_shard_count() -> usize {
+    std::thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+/// Serializes as the sequence of per-shard interners, each in its own
+/// [`crate::Interner::intern_id`] order, so that [`ShardedId`] handles
+/// serialized alongside this interner remain valid after a deserialize
+/// round-trip.
+#[cfg(feature = "serde")]
+impl<T: Hash + Eq + serde::Serialize> serde::Serialize for Interner<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.shards.serialize(serializer)
+    }
+}
+
+/// Deserializes the sequence of per-shard interners produced by [`Serialize`](serde::Serialize),
+/// restoring the same shard count (and thus the same `shift`) so that [`ShardedId`] handles
+/// serialized alongside this interner resolve to the same values.
+#[cfg(feature = "serde")]
+impl<'de, T: Hash + Eq + serde::Deserialize<'de>> serde::Deserialize<'de> for Interner<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let shards = Box::<[crate::Interner<T>]>::deserialize(deserializer)?;
+        let shift = 64 - shards.len().trailing_zeros();
+        Ok(Self { shards, shift })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn addr() {
+        let interner = Interner::with_shards(4);
+
+        let a1: *const _ = interner.intern(1);
+        let b1: *const _ = interner.intern(1);
+        interner.intern(2);
+
+        assert!(interner.try_resolve(&1) == Some(&1));
+        assert_eq!(a1.addr(), b1.addr());
+    }
+
+    #[test]
+    fn interned_handles() {
+        let interner = Interner::with_shards(4);
+
+        let a = interner.intern_id(1);
+        let b = interner.intern_id(1);
+        let c = interner.intern_id(2);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(interner.resolve(a), &1);
+        assert_eq!(interner.resolve(c), &2);
+    }
+}
@@ -8,22 +8,22 @@ use {
     },
 };
 
-pub struct Interner<T> {
-    inner: RwLock<crate::Interner<T>>,
+pub struct Interner<T: ?Sized> {
+    inner: RwLock<crate::unsync::Interner<T>>,
 }
 
-impl<T> Default for Interner<T> {
+impl<T: ?Sized> Default for Interner<T> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<T> Interner<T> {
+impl<T: ?Sized> Interner<T> {
     /// Creates an empty Interner.
     /// The current implementation does not allocate
     #[must_use]
     pub const fn new() -> Self {
-        Self { inner: RwLock::new(crate::Interner::new()) }
+        Self { inner: RwLock::new(crate::unsync::Interner::new()) }
     }
 
     /// Returns the number of entries in the interner
@@ -79,9 +79,66 @@ impl<T: Hash + Eq> Interner<T> {
         let inner = self.inner.write().unwrap();
         unsafe { longer(inner.insert(hash, value)) }
     }
+
+    /// Resolves a handle previously returned by [`Interner::intern_id`] back into a reference.
+    #[expect(clippy::missing_panics_doc)]
+    pub fn resolve(&self, id: crate::Interned<T>) -> &T {
+        unsafe { longer(self.inner.read().unwrap().resolve(id)) }
+    }
+
+    /// Interns `value`, returning a cheap `Copy` handle instead of a reference.
+    #[expect(clippy::missing_panics_doc, clippy::readonly_write_lock)]
+    pub fn intern_id(&self, value: T) -> crate::Interned<T> {
+        let hash = FxBuildHasher.hash_one(&value);
+
+        let inner = self.inner.read().unwrap();
+        if let Some(id) = inner.try_resolve_id_with(&value, hash) {
+            return id;
+        }
+
+        drop(inner);
+        let inner = self.inner.write().unwrap();
+
+        // try again in case another thread inserted a value in between the drop(_) and the .write().
+        if let Some(id) = inner.try_resolve_id_with(&value, hash) {
+            return id;
+        }
+
+        crate::Interned::from_index(inner.insert_index(hash, value))
+    }
+}
+
+impl<T: ?Sized + crate::InternRef> Interner<T> {
+    /// Will return a reference to an equivalent value if it already exists
+    #[expect(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn try_resolve_ref(&self, value: &T) -> Option<&T> {
+        self.inner.read().unwrap().try_resolve_ref(value).map(|cached| unsafe { longer(cached) })
+    }
+
+    /// Returns a reference to either the value provided, or an equivalent value that was
+    /// already inserted, copying `value`'s content into the arena rather than taking
+    /// ownership of it.
+    #[expect(clippy::missing_panics_doc, clippy::readonly_write_lock)]
+    pub fn intern_ref(&self, value: &T) -> &T {
+        let inner = self.inner.read().unwrap();
+        if let Some(cached) = inner.try_resolve_ref(value) {
+            return unsafe { longer(cached) };
+        }
+
+        drop(inner);
+        let inner = self.inner.write().unwrap();
+
+        // try again in case another thread inserted an equal value in between the drop(_) and the .write().
+        if let Some(cached) = inner.try_resolve_ref(value) {
+            return unsafe { longer(cached) };
+        }
+
+        unsafe { longer(inner.intern_ref(value)) }
+    }
 }
 
-impl<T: fmt::Debug> fmt::Debug for Interner<T> {
+impl<T: ?Sized + fmt::Debug + crate::DebugEntry> fmt::Debug for Interner<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let Ok(inner) = self.inner.try_read() else {
             return f.debug_set().finish_non_exhaustive();
@@ -90,12 +147,32 @@ impl<T: fmt::Debug> fmt::Debug for Interner<T> {
     }
 }
 
-unsafe fn longer<'b, T>(short: &T) -> &'b T {
+unsafe fn longer<'b, T: ?Sized>(short: &T) -> &'b T {
     unsafe { std::mem::transmute(short) }
 }
 
-unsafe impl<T: Send> Send for Interner<T> {}
-unsafe impl<T: Sync> Sync for Interner<T> {}
+unsafe impl<T: ?Sized + Send> Send for Interner<T> {}
+unsafe impl<T: ?Sized + Sync> Sync for Interner<T> {}
+
+/// Serializes as the sequence of distinct values currently interned, in
+/// [`Interner::intern_id`] order, so that [`crate::Interned`] handles serialized
+/// alongside this interner remain valid after a deserialize round-trip.
+#[cfg(feature = "serde")]
+impl<T: Hash + Eq + serde::Serialize> serde::Serialize for Interner<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.inner.read().unwrap().serialize(serializer)
+    }
+}
+
+/// Deserializes a sequence of values, re-interning each one into a fresh
+/// interner so that indices (and thus any [`crate::Interned`] handles) are
+/// restored in the same order they were serialized.
+#[cfg(feature = "serde")]
+impl<'de, T: Hash + Eq + serde::Deserialize<'de>> serde::Deserialize<'de> for Interner<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self { inner: RwLock::new(crate::unsync::Interner::deserialize(deserializer)?) })
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -125,4 +202,30 @@ mod tests {
         let array = interner.intern(Type::Array(int));
         println!("{array:?}");
     }
+
+    #[test]
+    fn interned_handles() {
+        let interner = Interner::new();
+
+        let a = interner.intern_id(1);
+        let b = interner.intern_id(1);
+        let c = interner.intern_id(2);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(interner.resolve(a), &1);
+        assert_eq!(interner.resolve(c), &2);
+    }
+
+    #[test]
+    fn intern_str() {
+        let interner: Interner<str> = Interner::new();
+
+        let a = interner.intern_ref("hello");
+        let b = interner.intern_ref("hello");
+        interner.intern_ref("world");
+
+        assert_eq!(a.as_ptr(), b.as_ptr());
+        assert_eq!(interner.try_resolve_ref("hello"), Some("hello"));
+    }
 }
@@ -1,4 +1,5 @@
 use {
+    crate::{DebugEntry, Interned, InternRef},
     bumpalo::Bump,
     hashbrown::HashTable,
     rustc_hash::FxBuildHasher,
@@ -12,36 +13,59 @@ use {
     },
 };
 
-pub struct Interner<T> {
+struct RefEntry {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+struct Table {
+    // keyed on a value's hash, payload is the index of its entry in `ids`
+    set: HashTable<u32>,
+    // `NonNull<u8>` is a reference into the arena, indexed by `Interned::index`
+    ids: Vec<NonNull<u8>>,
+    // storage for values interned through `intern_ref`, kept separate since those
+    // need a length alongside the pointer to reconstruct a fat pointer
+    ref_set: HashTable<u32>,
+    ref_entries: Vec<RefEntry>,
+}
+
+impl Table {
+    const fn new() -> Self {
+        Self {
+            set: HashTable::new(),
+            ids: Vec::new(),
+            ref_set: HashTable::new(),
+            ref_entries: Vec::new(),
+        }
+    }
+}
+
+pub struct Interner<T: ?Sized> {
     // an interner must be covariant in `T`
     __marker: PhantomData<T>,
-    // UnsafeCell for interior mutability, the NonNull<u8> is a reference into the arena.
+    // UnsafeCell for interior mutability, the ids vec holds references into the arena.
     // It uses u8 instead of T to avoid making T invariant
-    set: UnsafeCell<HashTable<NonNull<u8>>>,
+    table: UnsafeCell<Table>,
     arena: OnceCell<Bump>,
 }
 
-impl<T> Default for Interner<T> {
+impl<T: ?Sized> Default for Interner<T> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<T> Interner<T> {
+impl<T: ?Sized> Interner<T> {
     /// Creates an empty Interner.
     /// The current implementation does not allocate
     #[must_use]
     pub const fn new() -> Self {
-        Self {
-            __marker: PhantomData,
-            set: UnsafeCell::new(HashTable::new()),
-            arena: OnceCell::new(),
-        }
+        Self { __marker: PhantomData, table: UnsafeCell::new(Table::new()), arena: OnceCell::new() }
     }
 
     /// Returns the number of entries in the interner
     pub fn len(&self) -> usize {
-        self.set().len()
+        self.table().ids.len() + self.table().ref_entries.len()
     }
 
     /// Returns `true` if the interner contains no elements
@@ -49,21 +73,23 @@ impl<T> Interner<T> {
         self.len() == 0
     }
 
-    // Inserts the value into the interner's arena without checking if the value already exists.
-    // Future calls to intern will not find the same value, use `intern_new` if you want that behaviour.
-    pub fn insert_arena(&self, value: T) -> &mut T {
-        self.arena.get_or_init(Bump::new).alloc(value)
-    }
-
-    fn set(&self) -> &HashTable<NonNull<u8>> {
+    fn table(&self) -> &Table {
         // Safety: mutable access is entirely contained without the Interners methods.
-        unsafe { self.set.get().as_ref().unwrap() }
+        unsafe { self.table.get().as_ref().unwrap() }
     }
 
     #[expect(clippy::mut_from_ref)]
-    fn set_mut(&self) -> &mut HashTable<NonNull<u8>> {
+    fn table_mut(&self) -> &mut Table {
         // Safety: mutable access is entirely contained without the Interners methods.
-        unsafe { self.set.get().as_mut().unwrap() }
+        unsafe { self.table.get().as_mut().unwrap() }
+    }
+}
+
+impl<T> Interner<T> {
+    // Inserts the value into the interner's arena without checking if the value already exists.
+    // Future calls to intern will not find the same value, use `intern_new` if you want that behaviour.
+    pub fn insert_arena(&self, value: T) -> &mut T {
+        self.arena.get_or_init(Bump::new).alloc(value)
     }
 }
 
@@ -73,17 +99,29 @@ impl<T: Hash + Eq> Interner<T> {
         T: Borrow<Q>,
         Q: ?Sized + Eq,
     {
-        self.set()
-            .find(hash, |cached| T::borrow(unsafe { cached.cast().as_ref() }) == value)
-            .map(|ptr| unsafe { ptr.cast().as_ref() })
+        let table = self.table();
+        table
+            .set
+            .find(hash, |&idx| T::borrow(unsafe { table.ids[idx as usize].cast().as_ref() }) == value)
+            .map(|&idx| unsafe { table.ids[idx as usize].cast().as_ref() })
     }
 
-    pub(crate) fn insert(&self, hash: u64, value: T) -> &T {
+    pub(crate) fn insert_index(&self, hash: u64, value: T) -> u32 {
         let arena = self.arena.get_or_init(Bump::new);
+        let ptr = NonNull::from(arena.alloc(value)).cast();
+
+        let Table { set, ids, .. } = self.table_mut();
+        let idx = u32::try_from(ids.len()).expect("too many interned values");
+        ids.push(ptr);
+        set.insert_unique(hash, idx, |&idx| unsafe {
+            FxBuildHasher.hash_one(ids[idx as usize].cast::<T>().as_ref())
+        });
+        idx
+    }
 
-        let cached = NonNull::from(arena.alloc(value)).cast();
-        self.set_mut().insert_unique(hash, cached, |t| FxBuildHasher.hash_one(t));
-        unsafe { cached.cast().as_ref() }
+    pub(crate) fn insert(&self, hash: u64, value: T) -> &T {
+        let idx = self.insert_index(hash, value);
+        unsafe { self.table().ids[idx as usize].cast().as_ref() }
     }
 
     /// Will return a reference to an equivalent value if it already exists
@@ -97,6 +135,12 @@ impl<T: Hash + Eq> Interner<T> {
         self.try_resolve_with(value, hash)
     }
 
+    /// Resolves a handle previously returned by [`Interner::intern_id`] back into a reference.
+    #[must_use]
+    pub fn resolve(&self, id: Interned<T>) -> &T {
+        unsafe { self.table().ids[id.index() as usize].cast().as_ref() }
+    }
+
     /// Returns a reference to either the value provided, or an equivalent value that was already inserted
     pub fn intern(&self, value: T) -> &T {
         let hash = FxBuildHasher.hash_one(&value);
@@ -108,6 +152,27 @@ impl<T: Hash + Eq> Interner<T> {
         self.insert(hash, value)
     }
 
+    pub(crate) fn try_resolve_id_with(&self, value: &T, hash: u64) -> Option<Interned<T>> {
+        let table = self.table();
+        table
+            .set
+            .find(hash, |&idx| unsafe { table.ids[idx as usize].cast::<T>().as_ref() } == value)
+            .map(|&idx| Interned::from_index(idx))
+    }
+
+    /// Interns `value`, returning a cheap [`Copy`](std::marker::Copy) handle instead of a reference.
+    ///
+    /// The handle can later be turned back into `&T` via [`Interner::resolve`].
+    pub fn intern_id(&self, value: T) -> Interned<T> {
+        let hash = FxBuildHasher.hash_one(&value);
+
+        if let Some(id) = self.try_resolve_id_with(&value, hash) {
+            return id;
+        }
+
+        Interned::from_index(self.insert_index(hash, value))
+    }
+
     /// Inserts the value into the interner without checking if the value already exists
     pub fn intern_new(&self, value: T) -> &T {
         let hash = FxBuildHasher.hash_one(&value);
@@ -115,10 +180,92 @@ impl<T: Hash + Eq> Interner<T> {
     }
 }
 
-impl<T: fmt::Debug> fmt::Debug for Interner<T> {
+impl<T: ?Sized + InternRef> Interner<T> {
+    /// Will return a reference to an equivalent value if it already exists
+    #[must_use]
+    pub fn try_resolve_ref(&self, value: &T) -> Option<&T> {
+        let table = self.table();
+        table
+            .ref_set
+            .find(FxBuildHasher.hash_one(value), |&idx| {
+                let entry = &table.ref_entries[idx as usize];
+                unsafe { T::from_raw_parts(entry.ptr, entry.len) == value }
+            })
+            .map(|&idx| {
+                let entry = &table.ref_entries[idx as usize];
+                unsafe { T::from_raw_parts(entry.ptr, entry.len) }
+            })
+    }
+
+    /// Returns a reference to either the value provided, or an equivalent value that was
+    /// already inserted, copying `value`'s content into the arena rather than taking
+    /// ownership of it.
+    #[expect(clippy::missing_panics_doc)]
+    pub fn intern_ref(&self, value: &T) -> &T {
+        let hash = FxBuildHasher.hash_one(value);
+
+        if let Some(cached) = self.try_resolve_ref(value) {
+            return cached;
+        }
+
+        let arena = self.arena.get_or_init(Bump::new);
+        let ptr = value.copy_into(arena);
+        let len = value.ref_len();
+
+        let Table { ref_set, ref_entries, .. } = self.table_mut();
+        let idx = u32::try_from(ref_entries.len()).expect("too many interned values");
+        ref_entries.push(RefEntry { ptr, len });
+        ref_set.insert_unique(hash, idx, |&idx| unsafe {
+            let entry = &ref_entries[idx as usize];
+            FxBuildHasher.hash_one(T::from_raw_parts(entry.ptr, entry.len))
+        });
+
+        unsafe { T::from_raw_parts(ptr, len) }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug + DebugEntry> fmt::Debug for Interner<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.set().fmt(f)
+        let table = self.table();
+        let mut debug_set = f.debug_set();
+        debug_set.entries(table.ids.iter().map(|&ptr| unsafe { T::debug_entry(ptr, 0) }));
+        debug_set.entries(
+            table.ref_entries.iter().map(|entry| unsafe { T::debug_entry(entry.ptr, entry.len) }),
+        );
+        debug_set.finish()
+    }
+}
+
+unsafe impl<T: ?Sized> Send for Interner<T> where T: Send {}
+
+/// Serializes as the sequence of distinct values currently interned, in
+/// [`Interner::intern_id`] order, so that [`Interned`] handles serialized
+/// alongside this interner remain valid after a deserialize round-trip.
+#[cfg(feature = "serde")]
+impl<T: Hash + Eq + serde::Serialize> serde::Serialize for Interner<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+
+        let table = self.table();
+        let mut seq = serializer.serialize_seq(Some(table.ids.len()))?;
+        for &ptr in &table.ids {
+            seq.serialize_element(unsafe { ptr.cast::<T>().as_ref() })?;
+        }
+        seq.end()
     }
 }
 
-unsafe impl<T> Send for Interner<T> where T: Send {}
+/// Deserializes a sequence of values, re-interning each one into a fresh
+/// interner so that indices (and thus any [`Interned`] handles) are
+/// restored in the same order they were serialized.
+#[cfg(feature = "serde")]
+impl<'de, T: Hash + Eq + serde::Deserialize<'de>> serde::Deserialize<'de> for Interner<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let interner = Self::new();
+        for value in values {
+            interner.intern_id(value);
+        }
+        Ok(interner)
+    }
+}